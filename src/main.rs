@@ -3,12 +3,14 @@
 //! `dfx-canister-counter` is a command-line tool for analyzing `dfx.json` files in Internet Computer projects.
 //! It counts the number of canisters defined in the project and provides a summary of canister types.
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Represents the command-line interface for the dfx-canister-counter tool.
 #[derive(Parser)]
@@ -17,6 +19,505 @@ struct Cli {
     /// Optional path to dfx.json file. Defaults to current directory.
     #[arg(short, long, default_value = ".")]
     path: String,
+
+    /// Evaluate a JSONPath expression against dfx.json and print the matched nodes,
+    /// instead of running the default canister-counting summary.
+    ///
+    /// Supports the usual JSONPath syntax, e.g. `$.canisters.*.dependencies[*]` or
+    /// `$.canisters[?(@.type=='motoko')]`.
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Validate each canister against its type's required fields and print a report,
+    /// instead of running the default canister-counting summary.
+    ///
+    /// Exits with a non-zero status if any error-severity issues are found, so this can
+    /// gate CI.
+    #[arg(long)]
+    validate: bool,
+
+    /// Output format for the default canister summary.
+    #[arg(short, long, value_enum, default_value = "text")]
+    output: OutputFormat,
+
+    /// Print the canister dependency graph's topological build order, instead of running the
+    /// default canister-counting summary.
+    #[arg(long)]
+    graph: bool,
+
+    /// When used with --graph, emit Graphviz DOT instead of a plain topological list.
+    #[arg(long)]
+    dot: bool,
+}
+
+/// Output format for the default canister-counting summary.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable lines (the default).
+    Text,
+    /// Pretty-printed JSON, for scripts and dashboards.
+    Json,
+    /// CSV: one row per canister, followed by a type-count summary section.
+    Csv,
+}
+
+/// Top-level structure of a `dfx.json` configuration file.
+///
+/// Only the fields this tool cares about are modeled as typed data: `networks`
+/// and `defaults` are kept as untyped JSON, and any other top-level fields are
+/// ignored, so we don't need to keep this struct in lockstep with every field
+/// dfx supports.
+#[derive(Debug, Deserialize)]
+pub struct DfxConfig {
+    /// Canister declarations, keyed by canister name. `IndexMap` preserves
+    /// the declaration order from the source file.
+    pub canisters: IndexMap<String, Canister>,
+    /// Network definitions, passed through untouched.
+    #[serde(default)]
+    pub networks: Option<Value>,
+    /// Project-wide defaults, passed through untouched.
+    #[serde(default)]
+    pub defaults: Option<Value>,
+}
+
+/// A single canister declaration within `dfx.json`.
+#[derive(Debug, Deserialize)]
+pub struct Canister {
+    /// The canister's type, e.g. `"motoko"`, `"rust"`, `"custom"`, `"pull"`.
+    #[serde(rename = "type")]
+    pub canister_type: Option<String>,
+    /// Path to the canister's Candid interface file.
+    #[serde(default)]
+    pub candid: Option<String>,
+    /// Entry-point source file (used by `motoko` canisters).
+    #[serde(default)]
+    pub main: Option<String>,
+    /// Path to a prebuilt Wasm module (used by `custom` canisters).
+    #[serde(default)]
+    pub wasm: Option<String>,
+    /// Principal of a `pull` canister.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Names of other canisters this canister depends on.
+    #[serde(default)]
+    pub dependencies: Option<Vec<String>>,
+    /// Cargo package name (used by `rust` canisters).
+    #[serde(default)]
+    pub package: Option<String>,
+    /// Legacy alias for `package` (used by older `rust` canister declarations).
+    #[serde(rename = "crate", default)]
+    pub crate_: Option<String>,
+    /// Build command(s) (used by `custom` canisters); may be a single string or a list.
+    #[serde(default)]
+    pub build: Option<Value>,
+    /// Source directories for static assets (used by `assets` canisters).
+    #[serde(default)]
+    pub source: Option<Vec<String>>,
+}
+
+/// A serializable summary of a dfx.json analysis, suitable for `--output json` or `--output csv`.
+#[derive(Debug, Serialize)]
+pub struct AnalysisReport {
+    /// Total number of canisters.
+    pub total: usize,
+    /// Canister count broken down by type.
+    pub type_counts: BTreeMap<String, u32>,
+    /// Per-canister summaries, in declaration order.
+    pub canisters: Vec<CanisterSummary>,
+}
+
+/// A single canister's summary within an [`AnalysisReport`].
+#[derive(Debug, Serialize)]
+pub struct CanisterSummary {
+    /// The canister's name.
+    pub name: String,
+    /// The canister's declared type, or `"unknown"` if absent.
+    pub canister_type: String,
+    /// Whether the canister declares a `candid` interface file.
+    pub has_candid: bool,
+    /// Names of the canisters this canister depends on.
+    pub dependencies: Vec<String>,
+}
+
+/// The severity of a [`ValidationIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The canister is misconfigured in a way that will fail to build or deploy.
+    Error,
+    /// The canister is suspicious but may still be valid.
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single problem found while validating a canister's configuration.
+#[derive(Debug)]
+pub struct ValidationIssue {
+    /// The name of the canister the issue applies to.
+    pub canister: String,
+    /// How serious the issue is.
+    pub severity: Severity,
+    /// A human-readable description of the issue.
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(canister: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            canister: canister.to_string(),
+            severity,
+            message: message.into(),
+        }
+    }
+
+    fn error(canister: &str, message: impl Into<String>) -> Self {
+        Self::new(canister, Severity::Error, message)
+    }
+
+    fn warning(canister: &str, message: impl Into<String>) -> Self {
+        Self::new(canister, Severity::Warning, message)
+    }
+}
+
+/// Returns `true` if `id` looks like the textual encoding of an IC principal: groups of five
+/// lowercase base32 characters (`a`-`z`, `2`-`7` — the RFC 4648 base32 alphabet excludes digits
+/// `0`, `1`, `8`, and `9`) separated by dashes, with a final group of one to five characters.
+fn looks_like_principal(id: &str) -> bool {
+    let groups: Vec<&str> = id.split('-').collect();
+    let Some((last, rest)) = groups.split_last() else {
+        return false;
+    };
+    let is_base32_group =
+        |g: &str| !g.is_empty() && g.chars().all(|c| matches!(c, 'a'..='z' | '2'..='7'));
+
+    !rest.is_empty()
+        && rest.iter().all(|g| g.len() == 5 && is_base32_group(g))
+        && last.len() <= 5
+        && is_base32_group(last)
+}
+
+/// Validates each canister in `config` against the required fields for its declared type.
+///
+/// # Rules
+///
+/// - `motoko` requires `main`.
+/// - `rust` requires `candid` and either `package` or the legacy `crate` alias.
+/// - `custom` requires `candid` and either `wasm` or `build`.
+/// - `pull` requires an `id` that looks like a valid principal.
+/// - `assets` requires `source`.
+/// - An unrecognized or missing `type` is reported as a warning.
+///
+/// # Example
+///
+/// ```
+/// use dfx_canister_counter::{validate_canisters, Canister, DfxConfig, Severity};
+/// use indexmap::IndexMap;
+///
+/// let mut canisters = IndexMap::new();
+/// canisters.insert(
+///     "backend".to_string(),
+///     Canister {
+///         canister_type: Some("motoko".to_string()),
+///         candid: None,
+///         main: None, // missing required field
+///         wasm: None,
+///         id: None,
+///         dependencies: None,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     },
+/// );
+/// canisters.insert(
+///     "internet-identity".to_string(),
+///     Canister {
+///         canister_type: Some("pull".to_string()),
+///         candid: None,
+///         main: None,
+///         wasm: None,
+///         // contains '8', which isn't a valid base32 digit
+///         id: Some("rdmx8-jaaaa-aaaaa-aaadq-cai".to_string()),
+///         dependencies: None,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     },
+/// );
+/// let config = DfxConfig {
+///     canisters,
+///     networks: None,
+///     defaults: None,
+/// };
+///
+/// let issues = validate_canisters(&config);
+/// assert_eq!(issues.len(), 2);
+/// assert!(issues.iter().all(|issue| issue.severity == Severity::Error));
+/// assert!(issues[0].message.contains("main"));
+/// assert!(issues[1].message.contains("principal"));
+/// ```
+pub fn validate_canisters(config: &DfxConfig) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (name, canister) in &config.canisters {
+        match canister.canister_type.as_deref() {
+            Some("motoko") => {
+                if canister.main.is_none() {
+                    issues.push(ValidationIssue::error(
+                        name,
+                        "motoko canister is missing required field 'main'",
+                    ));
+                }
+            }
+            Some("rust") => {
+                if canister.candid.is_none() {
+                    issues.push(ValidationIssue::error(
+                        name,
+                        "rust canister is missing required field 'candid'",
+                    ));
+                }
+                if canister.package.is_none() && canister.crate_.is_none() {
+                    issues.push(ValidationIssue::error(
+                        name,
+                        "rust canister is missing required field 'package' (or legacy 'crate')",
+                    ));
+                }
+            }
+            Some("custom") => {
+                if canister.candid.is_none() {
+                    issues.push(ValidationIssue::error(
+                        name,
+                        "custom canister is missing required field 'candid'",
+                    ));
+                }
+                if canister.wasm.is_none() && canister.build.is_none() {
+                    issues.push(ValidationIssue::error(
+                        name,
+                        "custom canister must set either 'wasm' or 'build'",
+                    ));
+                }
+            }
+            Some("pull") => match &canister.id {
+                Some(id) if looks_like_principal(id) => {}
+                Some(id) => issues.push(ValidationIssue::error(
+                    name,
+                    format!("pull canister's 'id' ({id}) does not look like a valid principal"),
+                )),
+                None => issues.push(ValidationIssue::error(
+                    name,
+                    "pull canister is missing required field 'id'",
+                )),
+            },
+            Some("assets") => {
+                if canister.source.is_none() {
+                    issues.push(ValidationIssue::error(
+                        name,
+                        "assets canister is missing required field 'source'",
+                    ));
+                }
+            }
+            Some(other) => issues.push(ValidationIssue::warning(
+                name,
+                format!("unrecognized canister type '{other}'"),
+            )),
+            None => issues.push(ValidationIssue::warning(
+                name,
+                "canister is missing a 'type' field",
+            )),
+        }
+    }
+
+    issues
+}
+
+/// A canister's `dependencies` entry that refers to a canister not declared in dfx.json.
+#[derive(Debug, Clone)]
+pub struct DanglingDependency {
+    /// The canister declaring the dependency.
+    pub canister: String,
+    /// The undeclared canister name it depends on.
+    pub dependency: String,
+}
+
+/// The canister dependency graph built from `dependencies` declarations in dfx.json.
+#[derive(Debug)]
+pub struct DependencyGraph {
+    /// Adjacency list: canister name -> names of the canisters it depends on.
+    pub edges: IndexMap<String, Vec<String>>,
+    /// A valid build order (dependencies before dependents), or `None` if the graph contains
+    /// a cycle.
+    pub build_order: Option<Vec<String>>,
+    /// Canister names left over after Kahn's algorithm runs out of in-degree-zero nodes: the
+    /// canisters in a dependency cycle, plus any canisters that depend on them. Empty when there
+    /// is no cycle.
+    pub cycle: Vec<String>,
+    /// Declared dependencies that reference a canister not present in dfx.json.
+    pub dangling: Vec<DanglingDependency>,
+}
+
+/// Builds a [`DependencyGraph`] from `config`'s `dependencies` declarations.
+///
+/// Dangling dependencies (referencing a canister that doesn't exist) are reported but do not
+/// prevent the rest of the graph from being built. The build order is computed via Kahn's
+/// algorithm: repeatedly remove canisters with no unresolved dependencies; canisters left over
+/// once no more can be removed are those in a dependency cycle or depending on one.
+///
+/// # Examples
+///
+/// A valid chain, with a dangling dependency reported alongside the build order:
+///
+/// ```
+/// use dfx_canister_counter::{build_dependency_graph, Canister, DfxConfig};
+/// use indexmap::IndexMap;
+///
+/// fn canister(dependencies: Option<Vec<String>>) -> Canister {
+///     Canister {
+///         canister_type: Some("motoko".to_string()),
+///         candid: None,
+///         main: None,
+///         wasm: None,
+///         id: None,
+///         dependencies,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     }
+/// }
+///
+/// let mut canisters = IndexMap::new();
+/// canisters.insert("a".to_string(), canister(None));
+/// canisters.insert("b".to_string(), canister(Some(vec!["a".to_string()])));
+/// canisters.insert(
+///     "c".to_string(),
+///     canister(Some(vec!["b".to_string(), "missing".to_string()])),
+/// );
+/// let config = DfxConfig {
+///     canisters,
+///     networks: None,
+///     defaults: None,
+/// };
+///
+/// let graph = build_dependency_graph(&config).unwrap();
+/// assert_eq!(
+///     graph.build_order,
+///     Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+/// );
+/// assert_eq!(graph.dangling.len(), 1);
+/// assert_eq!(graph.dangling[0].dependency, "missing");
+/// ```
+///
+/// A cycle leaves no valid build order:
+///
+/// ```
+/// use dfx_canister_counter::{build_dependency_graph, Canister, DfxConfig};
+/// use indexmap::IndexMap;
+///
+/// fn canister(dependencies: Option<Vec<String>>) -> Canister {
+///     Canister {
+///         canister_type: Some("motoko".to_string()),
+///         candid: None,
+///         main: None,
+///         wasm: None,
+///         id: None,
+///         dependencies,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     }
+/// }
+///
+/// let mut canisters = IndexMap::new();
+/// canisters.insert("a".to_string(), canister(Some(vec!["b".to_string()])));
+/// canisters.insert("b".to_string(), canister(Some(vec!["a".to_string()])));
+/// let config = DfxConfig {
+///     canisters,
+///     networks: None,
+///     defaults: None,
+/// };
+///
+/// let graph = build_dependency_graph(&config).unwrap();
+/// assert!(graph.build_order.is_none());
+/// assert_eq!(graph.cycle.len(), 2);
+/// ```
+pub fn build_dependency_graph(config: &DfxConfig) -> Result<DependencyGraph> {
+    let mut edges: IndexMap<String, Vec<String>> = config
+        .canisters
+        .keys()
+        .map(|name| (name.clone(), Vec::new()))
+        .collect();
+    let mut dependents: IndexMap<String, Vec<String>> = config
+        .canisters
+        .keys()
+        .map(|name| (name.clone(), Vec::new()))
+        .collect();
+    let mut dangling = Vec::new();
+
+    for (name, canister) in &config.canisters {
+        for dep in canister.dependencies.iter().flatten() {
+            if !config.canisters.contains_key(dep) {
+                dangling.push(DanglingDependency {
+                    canister: name.clone(),
+                    dependency: dep.clone(),
+                });
+                continue;
+            }
+            edges.get_mut(name).unwrap().push(dep.clone());
+            dependents.get_mut(dep).unwrap().push(name.clone());
+        }
+    }
+
+    let mut in_degree: IndexMap<String, usize> = edges
+        .iter()
+        .map(|(name, deps)| (name.clone(), deps.len()))
+        .collect();
+
+    let mut queue: VecDeque<String> = in_degree
+        .iter()
+        .filter(|(_, &degree)| degree == 0)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let mut build_order = Vec::with_capacity(config.canisters.len());
+    while let Some(name) = queue.pop_front() {
+        build_order.push(name.clone());
+        for dependent in &dependents[&name] {
+            let degree = in_degree.get_mut(dependent).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                queue.push_back(dependent.clone());
+            }
+        }
+    }
+
+    let (build_order, cycle) = if build_order.len() == config.canisters.len() {
+        (Some(build_order), Vec::new())
+    } else {
+        let built: std::collections::HashSet<&String> = build_order.iter().collect();
+        let cycle = config
+            .canisters
+            .keys()
+            .filter(|name| !built.contains(name))
+            .cloned()
+            .collect();
+        (None, cycle)
+    };
+
+    Ok(DependencyGraph {
+        edges,
+        build_order,
+        cycle,
+        dangling,
+    })
 }
 
 /// Main function to run the dfx-canister-counter tool.
@@ -30,6 +531,7 @@ struct Cli {
 /// # Errors
 ///
 /// This function will return an error if:
+/// - No dfx.json can be found in `--path` or any of its parent directories.
 /// - The dfx.json file cannot be read or parsed.
 /// - The 'canisters' field is missing from the dfx.json file.
 ///
@@ -42,56 +544,301 @@ struct Cli {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    let path = Path::new(&cli.path).join("dfx.json");
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("Could not read dfx.json from {:?}", path))?;
+    let dfx_json_path = find_dfx_json(Path::new(&cli.path))?;
+    let project_root = dfx_json_path
+        .parent()
+        .context("dfx.json has no parent directory")?;
+    // Printed to stderr, not stdout, so it never mixes into the JSON/CSV/DOT payloads that
+    // --query, --output json/csv, and --graph --dot write to stdout for piping.
+    eprintln!("Project root: {}", project_root.display());
 
-    let json: Value = serde_json::from_str(&content).context("Failed to parse dfx.json")?;
+    if let Some(query) = &cli.query {
+        let json = read_dfx_json_value(&cli.path)?;
+        return run_query(&json, query);
+    }
 
-    let canisters = json["canisters"]
-        .as_object()
-        .context("No 'canisters' field found in dfx.json")?;
+    if cli.validate {
+        let config = read_dfx_json(&cli.path)?;
+        return print_validation_report(&config);
+    }
 
-    let mut type_count: HashMap<String, u32> = HashMap::new();
+    if cli.graph {
+        let config = read_dfx_json(&cli.path)?;
+        let graph = build_dependency_graph(&config)?;
+        return print_dependency_graph(&graph, cli.dot);
+    }
 
-    for (name, canister) in canisters {
-        let canister_type = canister["type"].as_str().unwrap_or("unknown");
-        *type_count.entry(canister_type.to_string()).or_insert(0) += 1;
-        println!("Canister: {}, Type: {}", name, canister_type);
+    let config = read_dfx_json(&cli.path)?;
+    let report = build_report(&config)?;
+    print_report(&report, cli.output)
+}
+
+/// Evaluates a JSONPath expression against the parsed dfx.json and prints each matched node.
+///
+/// Returns an error if zero nodes match, so the tool composes as a predicate in scripts
+/// (e.g. `dfx-canister-counter --query "$.canisters[?(@.type=='pull')]" || echo "none found"`).
+fn run_query(json: &Value, query: &str) -> Result<()> {
+    let results =
+        jsonpath_lib::select(json, query).with_context(|| format!("Invalid JSONPath: {query}"))?;
+
+    if results.is_empty() {
+        bail!("No matches for JSONPath query: {query}");
+    }
+
+    for value in &results {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    }
+
+    Ok(())
+}
+
+/// Builds an [`AnalysisReport`] from a parsed `DfxConfig`, reusing [`analyze_canisters`] for the
+/// type-count totals.
+fn build_report(config: &DfxConfig) -> Result<AnalysisReport> {
+    let (total, type_count) = analyze_canisters(config)?;
+    let type_counts: BTreeMap<String, u32> = type_count.into_iter().collect();
+
+    let canisters = config
+        .canisters
+        .iter()
+        .map(|(name, canister)| CanisterSummary {
+            name: name.clone(),
+            canister_type: canister
+                .canister_type
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            has_candid: canister.candid.is_some(),
+            dependencies: canister.dependencies.clone().unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(AnalysisReport {
+        total,
+        type_counts,
+        canisters,
+    })
+}
+
+/// Prints an [`AnalysisReport`] in the requested [`OutputFormat`].
+fn print_report(report: &AnalysisReport, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for canister in &report.canisters {
+                println!(
+                    "Canister: {}, Type: {}",
+                    canister.name, canister.canister_type
+                );
+            }
+
+            println!("\nTotal number of canisters: {}", report.total);
+            println!("\nCanister types summary:");
+            for (type_name, count) in &report.type_counts {
+                println!("  {}: {}", type_name, count);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(report)?);
+        }
+        OutputFormat::Csv => {
+            print!("{}", canisters_csv(report)?);
+            println!();
+            print!("{}", type_counts_csv(report)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders the per-canister rows of an [`AnalysisReport`] as CSV, via the `csv` crate so
+/// fields containing commas, quotes, or newlines (e.g. an arbitrary `canister_type` from a
+/// malformed dfx.json) are quoted/escaped correctly instead of misaligning columns.
+fn canisters_csv(report: &AnalysisReport) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(vec![]);
+
+    writer.write_record(["name", "type", "has_candid", "dependencies"])?;
+    for canister in &report.canisters {
+        writer.write_record([
+            canister.name.as_str(),
+            canister.canister_type.as_str(),
+            &canister.has_candid.to_string(),
+            &canister.dependencies.join(";"),
+        ])?;
+    }
+
+    String::from_utf8(writer.into_inner()?).context("Generated CSV was not valid UTF-8")
+}
+
+/// Renders an [`AnalysisReport`]'s type-count summary as CSV.
+fn type_counts_csv(report: &AnalysisReport) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(vec![]);
+
+    writer.write_record(["type", "count"])?;
+    for (type_name, count) in &report.type_counts {
+        writer.write_record([type_name.as_str(), &count.to_string()])?;
+    }
+
+    String::from_utf8(writer.into_inner()?).context("Generated CSV was not valid UTF-8")
+}
+
+/// Prints a [`DependencyGraph`]'s build order (or Graphviz DOT, if `dot` is set).
+///
+/// Dangling dependencies are printed to stderr, never stdout, so they don't corrupt the DOT
+/// output when `dot` is set or get mixed into a piped build-order list. Returns an error if the
+/// graph contains a dependency cycle, since no valid build order exists.
+fn print_dependency_graph(graph: &DependencyGraph, dot: bool) -> Result<()> {
+    if !graph.dangling.is_empty() {
+        eprintln!("Dangling dependencies:");
+        for dangling in &graph.dangling {
+            eprintln!(
+                "  {} depends on undeclared canister {}",
+                dangling.canister, dangling.dependency
+            );
+        }
     }
 
-    println!("\nTotal number of canisters: {}", canisters.len());
-    println!("\nCanister types summary:");
-    for (type_name, count) in type_count.iter() {
-        println!("  {}: {}", type_name, count);
+    let Some(build_order) = &graph.build_order else {
+        bail!(
+            "Dependency cycle detected among canisters: {}",
+            graph.cycle.join(", ")
+        );
+    };
+
+    if dot {
+        println!("digraph canisters {{");
+        for (name, deps) in &graph.edges {
+            if deps.is_empty() {
+                println!("  \"{name}\";");
+            }
+            for dep in deps {
+                println!("  \"{name}\" -> \"{dep}\";");
+            }
+        }
+        println!("}}");
+    } else {
+        println!("Build order:");
+        for (i, name) in build_order.iter().enumerate() {
+            println!("  {}. {}", i + 1, name);
+        }
     }
 
     Ok(())
 }
 
+/// Runs [`validate_canisters`] over `config` and prints a report grouped by severity.
+///
+/// Returns an error (and thus a non-zero exit code) if any error-severity issues were found.
+fn print_validation_report(config: &DfxConfig) -> Result<()> {
+    let issues = validate_canisters(config);
+
+    if issues.is_empty() {
+        println!("All canisters passed validation.");
+        return Ok(());
+    }
+
+    let (errors, warnings): (Vec<_>, Vec<_>) = issues
+        .iter()
+        .partition(|issue| issue.severity == Severity::Error);
+
+    if !errors.is_empty() {
+        println!("Errors:");
+        for issue in &errors {
+            println!(
+                "  [{}] {}: {}",
+                issue.severity, issue.canister, issue.message
+            );
+        }
+    }
+
+    if !warnings.is_empty() {
+        println!("Warnings:");
+        for issue in &warnings {
+            println!(
+                "  [{}] {}: {}",
+                issue.severity, issue.canister, issue.message
+            );
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!(
+            "Validation failed with {} error(s) and {} warning(s)",
+            errors.len(),
+            warnings.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Locates the `dfx.json` for the project containing `start`.
+///
+/// Mirrors how `dfx` itself resolves a project's root: starting from `start`, walk up the
+/// directory tree until a `dfx.json` is found, so the tool works from any subdirectory of a
+/// project rather than only from the directory containing `dfx.json`.
+///
+/// # Errors
+///
+/// Returns an error if `start` cannot be canonicalized, or if no `dfx.json` is found before
+/// reaching the filesystem root.
+fn find_dfx_json(start: &Path) -> Result<PathBuf> {
+    let start = start
+        .canonicalize()
+        .with_context(|| format!("Could not resolve path {:?}", start))?;
+
+    let mut dir = start.as_path();
+    loop {
+        let candidate = dir.join("dfx.json");
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+
+        dir = dir.parent().with_context(|| {
+            format!(
+                "{:?} is not inside a dfx project (no dfx.json found in any parent directory)",
+                start
+            )
+        })?;
+    }
+}
+
 /// Reads and parses the dfx.json file from the given path.
 ///
 /// # Arguments
 ///
-/// * `path` - A string slice that holds the path to the directory containing dfx.json.
+/// * `path` - A string slice that holds the path to search for dfx.json, starting from this
+///   directory and walking up to parent directories if necessary.
 ///
 /// # Returns
 ///
-/// Returns a `Result` containing the parsed JSON value if successful, or an error if the file
-/// cannot be read or parsed.
-fn read_dfx_json(path: &str) -> Result<Value> {
-    let path = Path::new(path).join("dfx.json");
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("Could not read dfx.json from {:?}", path))?;
+/// Returns a `Result` containing the deserialized `DfxConfig` if successful, or an error if the
+/// file cannot be found, read, or does not match the expected shape.
+fn read_dfx_json(path: &str) -> Result<DfxConfig> {
+    let dfx_json_path = find_dfx_json(Path::new(path))?;
+    let content = fs::read_to_string(&dfx_json_path)
+        .with_context(|| format!("Could not read dfx.json from {:?}", dfx_json_path))?;
 
     serde_json::from_str(&content).context("Failed to parse dfx.json")
 }
 
-/// Analyzes the canisters defined in the parsed dfx.json.
+/// Reads and parses the dfx.json file as a raw `serde_json::Value`, for use by `--query` mode
+/// where we want to walk the untyped JSON tree rather than the strongly-typed `DfxConfig`.
+fn read_dfx_json_value(path: &str) -> Result<Value> {
+    let dfx_json_path = find_dfx_json(Path::new(path))?;
+    let content = fs::read_to_string(&dfx_json_path)
+        .with_context(|| format!("Could not read dfx.json from {:?}", dfx_json_path))?;
+
+    serde_json::from_str(&content).context("Failed to parse dfx.json")
+}
+
+/// Analyzes the canisters defined in a parsed `DfxConfig`.
 ///
 /// # Arguments
 ///
-/// * `json` - A reference to the parsed JSON value of dfx.json.
+/// * `config` - A reference to the deserialized dfx.json configuration.
 ///
 /// # Returns
 ///
@@ -99,32 +846,66 @@ fn read_dfx_json(path: &str) -> Result<Value> {
 /// - The total number of canisters
 /// - A HashMap with canister types as keys and their counts as values
 ///
-/// Returns an error if the 'canisters' field is missing or not an object.
-///
 /// # Example
 ///
 /// ```
-/// use serde_json::json;
+/// use dfx_canister_counter::{analyze_canisters, Canister, DfxConfig};
+/// use indexmap::IndexMap;
 /// use std::collections::HashMap;
 ///
-/// let json = json!({
-///     "canisters": {
-///         "web3disk": {
-///             "type": "custom",
-///             "candid": "src/distributed/web3disk/web3disk.did"
-///         },
-///         "web3disk_service_backend": {
-///             "type": "motoko",
-///             "main": "src/web3disk_service_backend/src/main.mo"
-///         },
-///         "internet-identity": {
-///             "type": "pull",
-///             "id": "rdmx6-jaaaa-aaaaa-aaadq-cai"
-///         }
-///     }
-/// });
+/// let mut canisters = IndexMap::new();
+/// canisters.insert(
+///     "web3disk".to_string(),
+///     Canister {
+///         canister_type: Some("custom".to_string()),
+///         candid: Some("src/distributed/web3disk/web3disk.did".to_string()),
+///         main: None,
+///         wasm: None,
+///         id: None,
+///         dependencies: None,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     },
+/// );
+/// canisters.insert(
+///     "web3disk_service_backend".to_string(),
+///     Canister {
+///         canister_type: Some("motoko".to_string()),
+///         candid: None,
+///         main: Some("src/web3disk_service_backend/src/main.mo".to_string()),
+///         wasm: None,
+///         id: None,
+///         dependencies: None,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     },
+/// );
+/// canisters.insert(
+///     "internet-identity".to_string(),
+///     Canister {
+///         canister_type: Some("pull".to_string()),
+///         candid: None,
+///         main: None,
+///         wasm: None,
+///         id: Some("rdmx6-jaaaa-aaaaa-aaadq-cai".to_string()),
+///         dependencies: None,
+///         package: None,
+///         crate_: None,
+///         build: None,
+///         source: None,
+///     },
+/// );
+/// let config = DfxConfig {
+///     canisters,
+///     networks: None,
+///     defaults: None,
+/// };
 ///
-/// let (count, type_counts) = dfx_canister_counter::analyze_canisters(&json).unwrap();
+/// let (count, type_counts) = analyze_canisters(&config).unwrap();
 /// assert_eq!(count, 3);
 ///
 /// let mut expected_counts = HashMap::new();
@@ -133,17 +914,13 @@ fn read_dfx_json(path: &str) -> Result<Value> {
 /// expected_counts.insert("pull".to_string(), 1);
 /// assert_eq!(type_counts, expected_counts);
 /// ```
-pub fn analyze_canisters(json: &Value) -> Result<(usize, HashMap<String, u32>)> {
-    let canisters = json["canisters"]
-        .as_object()
-        .context("No 'canisters' field found in dfx.json")?;
-
+pub fn analyze_canisters(config: &DfxConfig) -> Result<(usize, HashMap<String, u32>)> {
     let mut type_count: HashMap<String, u32> = HashMap::new();
 
-    for (_name, canister) in canisters {
-        let canister_type = canister["type"].as_str().unwrap_or("unknown");
+    for canister in config.canisters.values() {
+        let canister_type = canister.canister_type.as_deref().unwrap_or("unknown");
         *type_count.entry(canister_type.to_string()).or_insert(0) += 1;
     }
 
-    Ok((canisters.len(), type_count))
+    Ok((config.canisters.len(), type_count))
 }